@@ -2,7 +2,10 @@ mod network;
 
 use clap::{App, Arg};
 
-use network::{load_network, load_traffic, model_traffic, worst_case_failure};
+use network::{
+    find_path, load_network, load_route_cache, load_traffic, model_traffic, network_hash, precompute_routes,
+    route_via_waypoints, save_route_cache, worst_case_failure, RoutingMode,
+};
 use std::error::Error;
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -16,11 +19,64 @@ fn main() -> Result<(), Box<dyn Error>> {
             .required(true)
             .takes_value(true)
             .help("Path to the traffic CSV file"))
+        .arg(Arg::with_name("mode")
+            .long("mode")
+            .takes_value(true)
+            .possible_values(&["dijkstra", "bfs", "greedy", "astar"])
+            .default_value("dijkstra")
+            .help("Routing algorithm used to compute shortest paths"))
+        .arg(Arg::with_name("factor")
+            .long("factor")
+            .takes_value(true)
+            .default_value("1.0")
+            .help("Scaling factor applied to the Greedy/A* coordinate heuristic"))
+        .arg(Arg::with_name("respect-capacity")
+            .long("respect-capacity")
+            .takes_value(false)
+            .help("Refuse to place a demand on a link whose remaining capacity is insufficient"))
+        .arg(Arg::with_name("paths")
+            .long("paths")
+            .takes_value(true)
+            .default_value("1")
+            .help("Number of shortest paths (Yen's k-shortest-paths) to split each demand across"))
+        .arg(Arg::with_name("precompute")
+            .long("precompute")
+            .takes_value(false)
+            .help("Precompute all-pairs shortest paths and reuse a cache file matching the current network"))
+        .arg(Arg::with_name("precomp-file")
+            .long("precomp-file")
+            .takes_value(true)
+            .help("Path to the route cache file (defaults to {network_hash}.idx)"))
+        .arg(Arg::with_name("waypoints")
+            .long("waypoints")
+            .takes_value(true)
+            .help("Comma-separated list of nodes a waypoint route query must traverse, in any order"))
+        .arg(Arg::with_name("waypoint-source")
+            .long("waypoint-source")
+            .takes_value(true)
+            .help("Source node for the waypoint route query (defaults to the first traffic demand's source)"))
+        .arg(Arg::with_name("waypoint-destination")
+            .long("waypoint-destination")
+            .takes_value(true)
+            .help("Destination node for the waypoint route query (defaults to the first traffic demand's destination)"))
+        .arg(Arg::with_name("pin-first-waypoint")
+            .long("pin-first-waypoint")
+            .takes_value(false)
+            .help("Force the first listed waypoint to be visited immediately after the source"))
+        .arg(Arg::with_name("pin-last-waypoint")
+            .long("pin-last-waypoint")
+            .takes_value(false)
+            .help("Force the last listed waypoint to be visited immediately before the destination"))
         .get_matches();
 
     // Get values from command-line arguments
     let network_file_path = matches.value_of("network").unwrap();
     let traffic_file_path = matches.value_of("traffic").unwrap();
+    let mode_arg = matches.value_of("mode").unwrap();
+    let mode = RoutingMode::from_str(mode_arg).unwrap();
+    let factor: f64 = matches.value_of("factor").unwrap().parse()?;
+    let respect_capacity = matches.is_present("respect-capacity");
+    let paths_per_demand: usize = matches.value_of("paths").unwrap().parse()?;
 
     // Load network
     let network = load_network(network_file_path)?;
@@ -30,13 +86,94 @@ fn main() -> Result<(), Box<dyn Error>> {
     let traffic_demands = load_traffic(traffic_file_path)?;
     println!("Traffic data loaded successfully.");
 
+    // Reuse a precomputed route cache when it matches the current network,
+    // otherwise precompute and save a fresh one.
+    let route_cache = if matches.is_present("precompute") {
+        let hash = network_hash(&network);
+        let cache_path = matches.value_of("precomp-file").map(|p| p.to_string()).unwrap_or_else(|| format!("{}.idx", hash));
+
+        let cache = match load_route_cache(&cache_path) {
+            Ok(cache) if cache.network_hash == hash => {
+                println!("Loaded matching route cache from {}.", cache_path);
+                cache
+            }
+            _ => {
+                println!("Precomputing route cache...");
+                let cache = precompute_routes(&network);
+                save_route_cache(&cache, &cache_path)?;
+                println!("Route cache saved to {}.", cache_path);
+                cache
+            }
+        };
+
+        Some(cache)
+    } else {
+        None
+    };
+
+    // Demonstrate the selected routing mode on the first demand, if any
+    if let Some(first_demand) = traffic_demands.first() {
+        match find_path(&network, &first_demand.source, &first_demand.destination, mode, factor) {
+            Some(path) => println!(
+                "Computed a {} hop path from {} to {} using {} mode.",
+                path.len(), first_demand.source, first_demand.destination, mode_arg
+            ),
+            None => println!(
+                "No path found from {} to {} using {} mode.",
+                first_demand.source, first_demand.destination, mode_arg
+            ),
+        }
+    }
+
     // Model traffic and generate utilization report
-    model_traffic(&network, &traffic_demands)?;
+    let unroutable_demands = model_traffic(
+        &network,
+        &traffic_demands,
+        mode,
+        factor,
+        respect_capacity,
+        paths_per_demand,
+        route_cache.as_ref(),
+    )?;
     println!("Utilization report generated successfully.");
+    if unroutable_demands > 0 {
+        println!("{} demand(s) could not be routed.", unroutable_demands);
+    }
 
     // Determine Worst Case Failure and generate report
-    worst_case_failure(&network, &traffic_demands)?;
+    worst_case_failure(&network, &traffic_demands, route_cache.as_ref())?;
     println!("Worst Case Failure report generated successfully.");
 
+    // Optional forced-transit query: route through a set of required
+    // intermediate nodes, in an optimal (or greedy, if too many) order.
+    if let Some(waypoints_arg) = matches.value_of("waypoints") {
+        let waypoints: Vec<String> = waypoints_arg.split(',').map(|w| w.trim().to_string()).collect();
+        let default_demand = traffic_demands.first();
+
+        let waypoint_source = matches
+            .value_of("waypoint-source")
+            .map(|s| s.to_string())
+            .or_else(|| default_demand.map(|d| d.source.clone()))
+            .ok_or("--waypoints requires --waypoint-source or at least one traffic demand")?;
+        let waypoint_destination = matches
+            .value_of("waypoint-destination")
+            .map(|s| s.to_string())
+            .or_else(|| default_demand.map(|d| d.destination.clone()))
+            .ok_or("--waypoints requires --waypoint-destination or at least one traffic demand")?;
+        let pin_first = matches.is_present("pin-first-waypoint");
+        let pin_last = matches.is_present("pin-last-waypoint");
+
+        match route_via_waypoints(&network, &waypoint_source, &waypoint_destination, &waypoints, pin_first, pin_last) {
+            Some(path) => println!(
+                "Computed a {} hop waypoint route from {} to {} via {:?}.",
+                path.len(), waypoint_source, waypoint_destination, waypoints
+            ),
+            None => println!(
+                "No waypoint route found from {} to {} via {:?}.",
+                waypoint_source, waypoint_destination, waypoints
+            ),
+        }
+    }
+
     Ok(())
 }
\ No newline at end of file
@@ -1,10 +1,13 @@
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 use std::error::Error;
 use std::fs::File;
 use std::io;
 use std::iter::Enumerate;
 use std::string::ToString;
 use csv::{ReaderBuilder, WriterBuilder};
+use permutohedron::LexicalPermutation;
+use rayon::prelude::*;
+use sha3::Digest;
 
 // Define Link struct
 #[derive(Debug)]
@@ -20,14 +23,68 @@ pub struct Link {
 #[derive(Debug)]
 pub struct Network {
     links: Vec<Link>,
+    // Optional (x, y, z) coordinates per node, used by the coordinate-based
+    // routing heuristics (Greedy, A*). Nodes without coordinates fall back
+    // to Dijkstra behavior (h = 0).
+    coords: HashMap<String, (f64, f64, f64)>,
+}
+
+// Selectable routing algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoutingMode {
+    Dijkstra,
+    Bfs,
+    Greedy,
+    AStar,
+}
+
+impl RoutingMode {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "dijkstra" => Some(RoutingMode::Dijkstra),
+            "bfs" => Some(RoutingMode::Bfs),
+            "greedy" => Some(RoutingMode::Greedy),
+            "astar" => Some(RoutingMode::AStar),
+            _ => None,
+        }
+    }
+}
+
+// A state used by the Greedy/A* search, ordered by the heuristic-aware
+// priority `f` instead of the raw accumulated cost `g` used by `State`.
+#[derive(Clone)]
+struct AStarState {
+    node: String,
+    g: usize,
+    f: f64,
+}
+
+impl PartialEq for AStarState {
+    fn eq(&self, other: &Self) -> bool {
+        self.f == other.f
+    }
+}
+
+impl Eq for AStarState {}
+
+impl Ord for AStarState {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.f.partial_cmp(&self.f).unwrap_or(std::cmp::Ordering::Equal)
+    }
+}
+
+impl PartialOrd for AStarState {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 // Define TrafficDemand struct
 #[derive(Debug)]
 pub struct TrafficDemand {
-    source: String,
-    destination: String,
-    demand: usize,
+    pub source: String,
+    pub destination: String,
+    pub demand: usize,
 }
 
 // Define a struct to represent a State for Dijkstra's algorithm
@@ -55,6 +112,7 @@ pub fn load_network(file_path: &str) -> Result<Network, Box<dyn Error>> {
     let mut rdr = ReaderBuilder::new().has_headers(false).from_reader(file);
 
     let mut links = Vec::new();
+    let mut coords: HashMap<String, (f64, f64, f64)> = HashMap::new();
 
     for result in rdr.records() {
         let record = result?;
@@ -65,10 +123,21 @@ pub fn load_network(file_path: &str) -> Result<Network, Box<dyn Error>> {
             capacity: record[2].parse()?, // Assuming capacity is the third column and contains integer values
             weight: record[3].parse()?,   // Assuming weight is the fourth column and contains integer values
         };
+
+        // Optional columns 4..9 carry start/end coordinates as
+        // start_x,start_y,start_z,end_x,end_y,end_z, used by the
+        // coordinate-based routing heuristics.
+        if record.len() >= 10 {
+            let start_coord = (record[4].parse()?, record[5].parse()?, record[6].parse()?);
+            let end_coord = (record[7].parse()?, record[8].parse()?, record[9].parse()?);
+            coords.insert(link.start.clone(), start_coord);
+            coords.insert(link.end.clone(), end_coord);
+        }
+
         links.push(link);
     }
 
-    Ok(Network { links })
+    Ok(Network { links, coords })
 }
 
 // Function to load traffic data from CSV
@@ -88,30 +157,142 @@ pub fn load_traffic(file_path: &str) -> Result<Vec<TrafficDemand>, Box<dyn Error
 
     Ok(traffic_demands)
 }
-// Function to model traffic load on the network and produce a report
-pub fn model_traffic(network: &Network, traffic_demands: &[TrafficDemand]) -> Result<(), Box<dyn Error>> {
-    let mut link_utilization: HashMap<(String, String), usize> = HashMap::new();
+// Routes each demand end-to-end using `mode`, accumulates utilization on
+// every link the path crosses, and writes `utilization_report.csv` plus
+// `overloaded_links.csv` for links whose cumulative utilization exceeds
+// their `capacity`. When `respect_capacity` is set, a demand is rejected
+// as unroutable rather than placed on a path with insufficient remaining
+// capacity on any of its links. Returns the number of unroutable demands.
+pub fn model_traffic(
+    network: &Network,
+    traffic_demands: &[TrafficDemand],
+    mode: RoutingMode,
+    factor: f64,
+    respect_capacity: bool,
+    paths_per_demand: usize,
+    route_cache: Option<&RouteCache>,
+) -> Result<usize, Box<dyn Error>> {
+    let mut link_utilization: Vec<usize> = vec![0; network.links.len()];
+    let mut unroutable_demands = 0;
 
     for demand in traffic_demands {
-        for link in &network.links {
-            if (link.start == demand.source) && (link.end == demand.destination) {
-                let entry = link_utilization.entry((link.start.clone(), link.end.clone())).or_insert(0);
-                *entry += demand.demand;
+        // A single path keeps the selected routing mode (Dijkstra/BFS/
+        // Greedy/A*); splitting across several paths for ECMP always
+        // relies on Yen's k-shortest-paths, which is Dijkstra-based. The
+        // route cache only holds plain-Dijkstra trees, so it is only
+        // consulted for the single-path, Dijkstra-mode case.
+        let paths: Vec<Vec<usize>> = if paths_per_demand <= 1 {
+            let cached_path = if mode == RoutingMode::Dijkstra {
+                route_cache.and_then(|cache| find_path_cached(network, cache, &demand.source, &demand.destination))
+            } else {
+                None
+            };
+
+            match cached_path.or_else(|| find_path(network, &demand.source, &demand.destination, mode, factor)) {
+                Some(path) => vec![path],
+                None => Vec::new(),
+            }
+        } else {
+            k_shortest_paths(network, &demand.source, &demand.destination, paths_per_demand)
+        };
+
+        if paths.is_empty() {
+            unroutable_demands += 1;
+            continue;
+        }
+
+        let residuals: Vec<i64> = paths
+            .iter()
+            .map(|path| {
+                path.iter()
+                    .map(|&link_id| {
+                        let link = &network.links[link_id];
+                        link.capacity as i64 - link_utilization[link_id] as i64
+                    })
+                    .min()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        // A zero-hop path (source == destination) crosses no links, so it
+        // needs no capacity at all; only check capacity when every path
+        // actually uses link capacity.
+        if respect_capacity && paths.iter().all(|path| !path.is_empty()) {
+            let total_residual: i64 = residuals.iter().map(|&r| r.max(0)).sum();
+            if total_residual < demand.demand as i64 {
+                unroutable_demands += 1;
+                continue;
+            }
+        }
+
+        let allocations = split_demand(&residuals, demand.demand);
+
+        // Paths can share links, so check the combined load per link.
+        let mut added: Vec<usize> = vec![0; network.links.len()];
+        for (path, allocation) in paths.iter().zip(allocations.iter()) {
+            for &link_id in path {
+                added[link_id] += allocation;
             }
         }
+
+        if respect_capacity
+            && added.iter().enumerate().any(|(link_id, &a)| a > 0 && link_utilization[link_id] + a > network.links[link_id].capacity)
+        {
+            unroutable_demands += 1;
+            continue;
+        }
+
+        for (link_id, a) in added.iter().enumerate() {
+            link_utilization[link_id] += a;
+        }
     }
 
     let mut utilization_report_writer = WriterBuilder::new().from_writer(File::create("utilization_report.csv")?);
-    utilization_report_writer.write_record(&["Start Node", "End Node", "Utilization"])?;
+    utilization_report_writer.write_record(&["Start Node", "End Node", "Utilization", "Capacity", "Utilization Ratio"])?;
+
+    let mut overloaded_report_writer = WriterBuilder::new().from_writer(File::create("overloaded_links.csv")?);
+    overloaded_report_writer.write_record(&["Start Node", "End Node", "Utilization", "Capacity", "Utilization Ratio"])?;
+
+    for (link_id, link) in network.links.iter().enumerate() {
+        let utilization = link_utilization[link_id];
+        let ratio = utilization as f64 / link.capacity as f64;
+
+        utilization_report_writer.write_record(&[
+            link.start.as_str(),
+            link.end.as_str(),
+            &utilization.to_string(),
+            &link.capacity.to_string(),
+            &format!("{:.4}", ratio),
+        ])?;
 
-    for ((start, end), utilization) in &link_utilization {
-        utilization_report_writer.write_record(&[start, end, &utilization.to_string()])?;
+        if utilization > link.capacity {
+            overloaded_report_writer.write_record(&[
+                link.start.as_str(),
+                link.end.as_str(),
+                &utilization.to_string(),
+                &link.capacity.to_string(),
+                &format!("{:.4}", ratio),
+            ])?;
+        }
     }
 
-    Ok(())
+    Ok(unroutable_demands)
 }
 
 pub fn dijkstra(network: &Network, source: &str, destination: &str) -> Option<Vec<usize>> {
+    dijkstra_restricted(network, source, destination, &HashSet::new(), &HashSet::new())
+}
+
+// Dijkstra's algorithm that ignores the links in `removed_links` and never
+// routes through the nodes in `removed_nodes`. Used by `k_shortest_paths`
+// to explore spur paths without mutating the network.
+fn dijkstra_restricted(
+    network: &Network,
+    source: &str,
+    destination: &str,
+    removed_links: &HashSet<usize>,
+    removed_nodes: &HashSet<String>,
+) -> Option<Vec<usize>> {
     let mut heap = BinaryHeap::new();
     let mut visited: HashMap<String, usize> = HashMap::new();
     let mut distances = HashMap::new();
@@ -127,21 +308,53 @@ pub fn dijkstra(network: &Network, source: &str, destination: &str) -> Option<Ve
 
     while let Some(State { node, cost }) = heap.pop() {
         if node == destination {
-            // Destination reached, reconstruct the path
-            let mut path = Vec::new();
-            let mut current_node = destination.to_string();
+            return Some(reconstruct_path(network, &visited, destination));
+        }
 
-            while let Some(link_id) = visited.get(&current_node) {
-                path.push(*link_id);
-                current_node = network.links[*link_id].start.clone();
-            }
+        if cost > *distances.get(&node).unwrap_or(&usize::MAX) {
+            // Skip this state if a shorter path to the node has already been found
+            continue;
+        }
 
-            path.reverse();
-            return Some(path);
+        for (link_id, link) in network.links.iter().enumerate() {
+            if link.start == node && !removed_links.contains(&link_id) && !removed_nodes.contains(&link.end) {
+                let next_node = link.end.clone();
+                let next_cost = cost + link.weight;
+
+                if next_cost < *distances.get(&next_node).unwrap_or(&usize::MAX) {
+                    distances.insert(next_node.clone(), next_cost);
+                    visited.insert(next_node.clone(), link_id);
+                    heap.push(State { node: next_node, cost: next_cost });
+                }
+            }
         }
+    }
 
+    None // No path found
+}
+
+fn path_weight(network: &Network, path: &[usize]) -> usize {
+    path.iter().map(|&link_id| network.links[link_id].weight).sum()
+}
+
+// Runs Dijkstra from `source` to every other node, returning the
+// predecessor link-id map used by `precompute_routes` to build the
+// per-source route cache.
+fn dijkstra_tree(network: &Network, source: &str) -> HashMap<String, usize> {
+    let mut heap = BinaryHeap::new();
+    let mut visited: HashMap<String, usize> = HashMap::new();
+    let mut distances = HashMap::new();
+
+    for link in &network.links {
+        distances.insert(link.start.clone(), usize::MAX);
+        distances.insert(link.end.clone(), usize::MAX);
+    }
+    distances.insert(source.to_string(), 0);
+
+    heap.push(State { node: source.to_string(), cost: 0 });
+
+    while let Some(State { node, cost }) = heap.pop() {
         if cost > *distances.get(&node).unwrap_or(&usize::MAX) {
-            // Skip this state if a shorter path to the node has already been found
             continue;
         }
 
@@ -159,24 +372,758 @@ pub fn dijkstra(network: &Network, source: &str, destination: &str) -> Option<Ve
         }
     }
 
-    None // No path found
+    visited
 }
 
-// Function to determine Worst Case Failure (WCF)
-pub fn worst_case_failure(network: &Network, traffic_demands: &[TrafficDemand]) -> Result<(), Box<dyn Error>> {
-    let mut wcf_report_writer = WriterBuilder::new().from_writer(File::create("wcf_report.csv")?);
+// An all-sources precomputed set of shortest-path trees, keyed by source
+// node, plus the network hash it was computed against. Serialized to
+// disk with `bincode` so repeated analyses of the same topology can skip
+// recomputing Dijkstra.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct RouteCache {
+    pub network_hash: String,
+    trees: HashMap<String, HashMap<String, usize>>,
+}
+
+// Computes a SHA3-256 digest over the sorted `(start, end, capacity,
+// weight)` link tuples, used as the route cache's key so a cache file is
+// only reused for the exact topology it was built from.
+pub fn network_hash(network: &Network) -> String {
+    let mut tuples: Vec<(String, String, usize, usize)> = network
+        .links
+        .iter()
+        .map(|link| (link.start.clone(), link.end.clone(), link.capacity, link.weight))
+        .collect();
+    tuples.sort();
 
+    let mut hasher = sha3::Sha3_256::new();
+    for (start, end, capacity, weight) in &tuples {
+        hasher.update(start.as_bytes());
+        hasher.update(b",");
+        hasher.update(end.as_bytes());
+        hasher.update(b",");
+        hasher.update(capacity.to_string().as_bytes());
+        hasher.update(b",");
+        hasher.update(weight.to_string().as_bytes());
+        hasher.update(b";");
+    }
+
+    format!("{:x}", hasher.finalize())
+}
+
+// Runs Dijkstra from every node in the network once and bundles the
+// resulting trees with the network's hash for later reuse.
+pub fn precompute_routes(network: &Network) -> RouteCache {
+    let mut nodes: HashSet<String> = HashSet::new();
     for link in &network.links {
-        let mut unreachable_nodes = Vec::new();
-        unreachable_nodes.push(&link.start);
-        unreachable_nodes.push(&link.end);
+        nodes.insert(link.start.clone());
+        nodes.insert(link.end.clone());
+    }
+
+    let trees = nodes.into_iter().map(|node| {
+        let tree = dijkstra_tree(network, &node);
+        (node, tree)
+    }).collect();
+
+    RouteCache { network_hash: network_hash(network), trees }
+}
+
+pub fn save_route_cache(cache: &RouteCache, file_path: &str) -> Result<(), Box<dyn Error>> {
+    let bytes = bincode::serialize(cache)?;
+    std::fs::write(file_path, bytes)?;
+    Ok(())
+}
+
+pub fn load_route_cache(file_path: &str) -> Result<RouteCache, Box<dyn Error>> {
+    let bytes = std::fs::read(file_path)?;
+    let cache = bincode::deserialize(&bytes)?;
+    Ok(cache)
+}
+
+// Looks up the precomputed path from `source` to `destination` in
+// `cache`, reconstructing it from the cached predecessor tree.
+pub fn find_path_cached(network: &Network, cache: &RouteCache, source: &str, destination: &str) -> Option<Vec<usize>> {
+    if source == destination {
+        return Some(Vec::new());
+    }
+
+    let tree = cache.trees.get(source)?;
+    if !tree.contains_key(destination) {
+        return None;
+    }
+
+    Some(reconstruct_path(network, tree, destination))
+}
+
+// A candidate path considered during Yen's algorithm, ordered by total
+// weight so the cheapest candidate is popped first from the `BinaryHeap`.
+#[derive(Clone, Eq, PartialEq)]
+struct PathCandidate {
+    path: Vec<usize>,
+    weight: usize,
+}
+
+impl Ord for PathCandidate {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.weight.cmp(&self.weight)
+    }
+}
+
+impl PartialOrd for PathCandidate {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Yen's k-shortest-paths algorithm, built on top of Dijkstra. Returns up
+// to `k` loopless paths from `source` to `destination`, ordered from
+// cheapest to most expensive, for splitting a demand across several
+// equal-cost (or near equal-cost) routes.
+pub fn k_shortest_paths(network: &Network, source: &str, destination: &str, k: usize) -> Vec<Vec<usize>> {
+    let mut found_paths: Vec<Vec<usize>> = Vec::new();
+    if k == 0 {
+        return found_paths;
+    }
+
+    let first_path = match dijkstra(network, source, destination) {
+        Some(path) => path,
+        None => return found_paths,
+    };
+    found_paths.push(first_path);
+
+    let mut candidates: BinaryHeap<PathCandidate> = BinaryHeap::new();
+
+    while found_paths.len() < k {
+        let prev_path = found_paths.last().unwrap().clone();
+
+        for i in 0..prev_path.len() {
+            let spur_node = if i == 0 {
+                source.to_string()
+            } else {
+                network.links[prev_path[i - 1]].end.clone()
+            };
+            let root_path = prev_path[..i].to_vec();
+
+            // Remove the edges that coincide with the prefix of any
+            // already-found path sharing this same root, so the spur
+            // search can't simply retrace a known path.
+            let mut removed_links: HashSet<usize> = HashSet::new();
+            for path in &found_paths {
+                if path.len() > i && path[..i] == root_path[..] {
+                    removed_links.insert(path[i]);
+                }
+            }
+
+            // Remove the root path's nodes (other than the spur node
+            // itself) so the spur search can't loop back through them.
+            let mut removed_nodes: HashSet<String> = HashSet::new();
+            for &link_id in &root_path {
+                removed_nodes.insert(network.links[link_id].start.clone());
+            }
+
+            if let Some(spur_path) = dijkstra_restricted(network, &spur_node, destination, &removed_links, &removed_nodes) {
+                let mut total_path = root_path.clone();
+                total_path.extend(spur_path);
 
-        for demand in traffic_demands {
-            if unreachable_nodes.contains(&&demand.source) || unreachable_nodes.contains(&&demand.destination) {
-                wcf_report_writer.write_record(&[&link.start, &link.end, &demand.source, &demand.destination])?;
+                if !found_paths.contains(&total_path) {
+                    let weight = path_weight(network, &total_path);
+                    candidates.push(PathCandidate { path: total_path, weight });
+                }
             }
         }
+
+        let next_path = loop {
+            match candidates.pop() {
+                Some(candidate) if !found_paths.contains(&candidate.path) => break Some(candidate.path),
+                Some(_) => continue,
+                None => break None,
+            }
+        };
+
+        match next_path {
+            Some(path) => found_paths.push(path),
+            None => break,
+        }
+    }
+
+    found_paths
+}
+
+// Splits `demand` across paths proportionally to each path's residual
+// capacity (the minimum remaining capacity along its links). Falls back
+// to an even split when no path has any residual capacity left, so the
+// resulting overload is still visible in the utilization report.
+fn split_demand(residuals: &[i64], demand: usize) -> Vec<usize> {
+    let total: i64 = residuals.iter().map(|&r| r.max(0)).sum();
+
+    let mut allocations: Vec<usize> = if total > 0 {
+        residuals
+            .iter()
+            .map(|&r| ((demand as f64) * (r.max(0) as f64) / (total as f64)).floor() as usize)
+            .collect()
+    } else {
+        vec![demand / residuals.len(); residuals.len()]
+    };
+
+    // The rounding remainder only goes to paths with residual capacity.
+    let eligible: Vec<usize> = (0..allocations.len()).filter(|&i| total <= 0 || residuals[i] > 0).collect();
+    let mut remainder = demand.saturating_sub(allocations.iter().sum());
+    let len = eligible.len();
+    let mut i = 0;
+    while remainder > 0 {
+        allocations[eligible[i % len]] += 1;
+        remainder -= 1;
+        i += 1;
+    }
+
+    allocations
+}
+
+// Breadth-first search: ignores link weight entirely and returns the path
+// with the fewest hops.
+fn bfs(network: &Network, source: &str, destination: &str) -> Option<Vec<usize>> {
+    let mut queue = VecDeque::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut visited: HashMap<String, usize> = HashMap::new();
+
+    seen.insert(source.to_string());
+    queue.push_back(source.to_string());
+
+    while let Some(node) = queue.pop_front() {
+        if node == destination {
+            return Some(reconstruct_path(network, &visited, destination));
+        }
+
+        for (link_id, link) in network.links.iter().enumerate() {
+            if link.start == node && !seen.contains(&link.end) {
+                seen.insert(link.end.clone());
+                visited.insert(link.end.clone(), link_id);
+                queue.push_back(link.end.clone());
+            }
+        }
+    }
+
+    None
+}
+
+// Straight-line distance from `node` to `destination`, scaled by `factor`.
+// Nodes missing coordinates contribute h = 0, which degrades A* to plain
+// Dijkstra for that part of the search.
+fn heuristic(network: &Network, node: &str, destination: &str, factor: f64) -> f64 {
+    match (network.coords.get(node), network.coords.get(destination)) {
+        (Some(&(x1, y1, z1)), Some(&(x2, y2, z2))) => {
+            let (dx, dy, dz) = (x1 - x2, y1 - y2, z1 - z2);
+            factor * (dx * dx + dy * dy + dz * dz).sqrt()
+        }
+        _ => 0.0,
+    }
+}
+
+// Shared engine for Greedy and A* search. Greedy orders the heap by the
+// heuristic `h` alone; A* orders by `f = g + h`, where `g` is the
+// accumulated link weight from the source.
+fn heuristic_search(
+    network: &Network,
+    source: &str,
+    destination: &str,
+    factor: f64,
+    greedy: bool,
+) -> Option<Vec<usize>> {
+    let mut heap = BinaryHeap::new();
+    let mut visited: HashMap<String, usize> = HashMap::new();
+    let mut best_g: HashMap<String, usize> = HashMap::new();
+
+    best_g.insert(source.to_string(), 0);
+    let h = heuristic(network, source, destination, factor);
+    heap.push(AStarState { node: source.to_string(), g: 0, f: h });
+
+    while let Some(AStarState { node, g, .. }) = heap.pop() {
+        if node == destination {
+            return Some(reconstruct_path(network, &visited, destination));
+        }
+
+        if g > *best_g.get(&node).unwrap_or(&usize::MAX) {
+            continue;
+        }
+
+        for (link_id, link) in network.links.iter().enumerate() {
+            if link.start == node {
+                let next_node = link.end.clone();
+                let next_g = g + link.weight;
+
+                if next_g < *best_g.get(&next_node).unwrap_or(&usize::MAX) {
+                    best_g.insert(next_node.clone(), next_g);
+                    visited.insert(next_node.clone(), link_id);
+                    let h = heuristic(network, &next_node, destination, factor);
+                    let f = if greedy { h } else { next_g as f64 + h };
+                    heap.push(AStarState { node: next_node, g: next_g, f });
+                }
+            }
+        }
+    }
+
+    None
+}
+
+// Reconstructs a path as a sequence of link ids by walking the
+// predecessor map built during a search, from `destination` back to the
+// (implicit) source.
+fn reconstruct_path(network: &Network, visited: &HashMap<String, usize>, destination: &str) -> Vec<usize> {
+    let mut path = Vec::new();
+    let mut current_node = destination.to_string();
+
+    while let Some(link_id) = visited.get(&current_node) {
+        path.push(*link_id);
+        current_node = network.links[*link_id].start.clone();
+    }
+
+    path.reverse();
+    path
+}
+
+// Finds a path from `source` to `destination` using the selected routing
+// mode. `factor` scales the coordinate-based heuristic used by Greedy and
+// A* (see `heuristic`); it is ignored by Dijkstra and BFS.
+pub fn find_path(
+    network: &Network,
+    source: &str,
+    destination: &str,
+    mode: RoutingMode,
+    factor: f64,
+) -> Option<Vec<usize>> {
+    match mode {
+        RoutingMode::Dijkstra => dijkstra(network, source, destination),
+        RoutingMode::Bfs => bfs(network, source, destination),
+        RoutingMode::Greedy => heuristic_search(network, source, destination, factor, true),
+        RoutingMode::AStar => heuristic_search(network, source, destination, factor, false),
+    }
+}
+
+// The outcome of rerouting every demand with a single link removed.
+struct LinkFailureResult {
+    failed_link: usize,
+    unroutable_demands: usize,
+    max_utilization_link: Option<usize>,
+    max_utilization_ratio: f64,
+}
+
+// Reroutes every demand with `failed_link_id` removed from the network
+// and reports the demands that become unroutable plus the link left most
+// overloaded by the reroute.
+fn simulate_link_failure(
+    network: &Network,
+    traffic_demands: &[TrafficDemand],
+    failed_link_id: usize,
+    route_cache: Option<&RouteCache>,
+) -> LinkFailureResult {
+    let mut removed_links = HashSet::new();
+    removed_links.insert(failed_link_id);
+    let removed_nodes = HashSet::new();
+
+    let mut link_utilization: Vec<usize> = vec![0; network.links.len()];
+    let mut unroutable_demands = 0;
+
+    for demand in traffic_demands {
+        // A cached path can be reused as-is unless it actually crosses
+        // the failed link, avoiding a full Dijkstra recompute for most
+        // demands during a repeated failure sweep.
+        let cached_path = route_cache
+            .and_then(|cache| find_path_cached(network, cache, &demand.source, &demand.destination))
+            .filter(|path| !path.contains(&failed_link_id));
+
+        let path = cached_path
+            .or_else(|| dijkstra_restricted(network, &demand.source, &demand.destination, &removed_links, &removed_nodes));
+
+        match path {
+            Some(path) => {
+                for link_id in path {
+                    link_utilization[link_id] += demand.demand;
+                }
+            }
+            None => unroutable_demands += 1,
+        }
+    }
+
+    let mut max_utilization_link = None;
+    let mut max_utilization_ratio = 0.0;
+
+    for (link_id, link) in network.links.iter().enumerate() {
+        let ratio = link_utilization[link_id] as f64 / link.capacity as f64;
+        if ratio > max_utilization_ratio {
+            max_utilization_ratio = ratio;
+            max_utilization_link = Some(link_id);
+        }
+    }
+
+    LinkFailureResult { failed_link: failed_link_id, unroutable_demands, max_utilization_link, max_utilization_ratio }
+}
+
+// Runs a single-link-failure analysis: for every link in turn, reroutes
+// all demands around it and records the demands that become unroutable
+// and the worst resulting overload. Writes `wcf_report.csv` with columns
+// `failed_link, unroutable_demands, max_utilization_link,
+// max_utilization_ratio`.
+pub fn worst_case_failure(
+    network: &Network,
+    traffic_demands: &[TrafficDemand],
+    route_cache: Option<&RouteCache>,
+) -> Result<(), Box<dyn Error>> {
+    let results: Vec<LinkFailureResult> = network
+        .links
+        .par_iter()
+        .enumerate()
+        .map(|(failed_link_id, _)| simulate_link_failure(network, traffic_demands, failed_link_id, route_cache))
+        .collect();
+
+    let results_by_link: HashMap<usize, &LinkFailureResult> = results.iter().map(|r| (r.failed_link, r)).collect();
+
+    let mut wcf_report_writer = WriterBuilder::new().from_writer(File::create("wcf_report.csv")?);
+    wcf_report_writer.write_record(&["failed_link", "unroutable_demands", "max_utilization_link", "max_utilization_ratio"])?;
+
+    for (link_id, link) in network.links.iter().enumerate() {
+        let result = results_by_link.get(&link_id).expect("every link was simulated");
+        let failed_link_label = format!("{}-{}", link.start, link.end);
+        let max_utilization_label = result
+            .max_utilization_link
+            .map(|id| format!("{}-{}", network.links[id].start, network.links[id].end))
+            .unwrap_or_else(|| "-".to_string());
+
+        wcf_report_writer.write_record(&[
+            failed_link_label.as_str(),
+            &result.unroutable_demands.to_string(),
+            max_utilization_label.as_str(),
+            &format!("{:.4}", result.max_utilization_ratio),
+        ])?;
+    }
+
+    if let Some(worst) = results.iter().max_by(|a, b| a.max_utilization_ratio.partial_cmp(&b.max_utilization_ratio).unwrap()) {
+        let link = &network.links[worst.failed_link];
+        println!(
+            "Worst single link failure: {}-{} (max utilization ratio {:.4}).",
+            link.start, link.end, worst.max_utilization_ratio
+        );
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+// Above this many free (unpinned) waypoints, exhaustively permuting
+// orderings is infeasible, so `route_via_waypoints` falls back to a
+// nearest-neighbor greedy ordering instead.
+const WAYPOINT_PERMUTATION_LIMIT: usize = 8;
+
+fn build_distance_matrix(network: &Network, stops: &[String]) -> HashMap<(String, String), (Vec<usize>, usize)> {
+    let mut matrix = HashMap::new();
+
+    for a in stops {
+        for b in stops {
+            if a != b {
+                if let Some(path) = dijkstra(network, a, b) {
+                    let weight = path_weight(network, &path);
+                    matrix.insert((a.clone(), b.clone()), (path, weight));
+                }
+            }
+        }
+    }
+
+    matrix
+}
+
+fn sequence_weight(matrix: &HashMap<(String, String), (Vec<usize>, usize)>, sequence: &[String]) -> Option<usize> {
+    sequence.windows(2).map(|pair| matrix.get(&(pair[0].clone(), pair[1].clone())).map(|(_, weight)| *weight)).sum()
+}
+
+fn concatenate_sequence(matrix: &HashMap<(String, String), (Vec<usize>, usize)>, sequence: &[String]) -> Option<Vec<usize>> {
+    let mut full_path = Vec::new();
+
+    for pair in sequence.windows(2) {
+        let (path, _) = matrix.get(&(pair[0].clone(), pair[1].clone()))?;
+        full_path.extend(path.iter().cloned());
+    }
+
+    Some(full_path)
+}
+
+// Greedily picks the nearest remaining waypoint at each step, starting
+// from `start`. Used once the number of free waypoints makes exhaustive
+// permutation infeasible. Returns `None` if, at some step, none of the
+// remaining waypoints is reachable from the current node, rather than
+// silently dropping them.
+fn nearest_neighbor_order(matrix: &HashMap<(String, String), (Vec<usize>, usize)>, start: &str, mut remaining: Vec<String>) -> Option<Vec<String>> {
+    let mut order = Vec::new();
+    let mut current = start.to_string();
+
+    while !remaining.is_empty() {
+        let nearest = remaining
+            .iter()
+            .enumerate()
+            .filter_map(|(i, node)| matrix.get(&(current.clone(), node.clone())).map(|(_, weight)| (i, *weight)))
+            .min_by_key(|&(_, weight)| weight);
+
+        let (idx, _) = nearest?;
+        let next = remaining.remove(idx);
+        current = next.clone();
+        order.push(next);
+    }
+
+    Some(order)
+}
+
+// Finds the cheapest route from `source` to `destination` that also
+// visits every node in `waypoints`. Builds a small distance matrix
+// between source, destination and the waypoints with the existing
+// Dijkstra, then tries every ordering of the "free" waypoints (those not
+// pinned by `pin_first`/`pin_last`) via a lexical permutation generator,
+// falling back to a nearest-neighbor greedy ordering when there are too
+// many to permute exhaustively.
+pub fn route_via_waypoints(
+    network: &Network,
+    source: &str,
+    destination: &str,
+    waypoints: &[String],
+    pin_first: bool,
+    pin_last: bool,
+) -> Option<Vec<usize>> {
+    if waypoints.is_empty() {
+        return dijkstra(network, source, destination);
+    }
+
+    let first_pinned = if pin_first { Some(waypoints[0].clone()) } else { None };
+    let last_pinned = if pin_last && waypoints.len() > usize::from(pin_first) {
+        Some(waypoints[waypoints.len() - 1].clone())
+    } else {
+        None
+    };
+
+    let free_waypoints: Vec<String> = waypoints
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !(pin_first && *i == 0 || pin_last && *i == waypoints.len() - 1))
+        .map(|(_, w)| w.clone())
+        .collect();
+
+    let mut stops: Vec<String> = vec![source.to_string(), destination.to_string()];
+    stops.extend(waypoints.iter().cloned());
+    stops.sort();
+    stops.dedup();
+
+    let matrix = build_distance_matrix(network, &stops);
+    let anchor_start = first_pinned.clone().unwrap_or_else(|| source.to_string());
+
+    let best_order = if free_waypoints.len() > WAYPOINT_PERMUTATION_LIMIT {
+        nearest_neighbor_order(&matrix, &anchor_start, free_waypoints)?
+    } else {
+        let mut indices: Vec<usize> = (0..free_waypoints.len()).collect();
+        let mut best: Option<(Vec<usize>, usize)> = None;
+
+        loop {
+            let mut sequence = vec![source.to_string()];
+            sequence.extend(first_pinned.clone());
+            sequence.extend(indices.iter().map(|&i| free_waypoints[i].clone()));
+            sequence.extend(last_pinned.clone());
+            sequence.push(destination.to_string());
+
+            if let Some(weight) = sequence_weight(&matrix, &sequence) {
+                if best.as_ref().is_none_or(|(_, best_weight)| weight < *best_weight) {
+                    best = Some((indices.clone(), weight));
+                }
+            }
+
+            if !indices.next_permutation() {
+                break;
+            }
+        }
+
+        let (indices, _) = best?;
+        indices.into_iter().map(|i| free_waypoints[i].clone()).collect()
+    };
+
+    let mut sequence = vec![source.to_string()];
+    sequence.extend(first_pinned);
+    sequence.extend(best_order);
+    sequence.extend(last_pinned);
+    sequence.push(destination.to_string());
+
+    concatenate_sequence(&matrix, &sequence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A -> B -> D (weight 10) and A -> C -> D (weight 2), with coordinates
+    // that make the straight-line heuristic admissible for the A*/Greedy
+    // search, used to check that every mode reconstructs a valid path.
+    fn diamond_network() -> Network {
+        let links = vec![
+            Link { link_id: 0, start: "A".into(), end: "B".into(), capacity: 100, weight: 5 },
+            Link { link_id: 1, start: "B".into(), end: "D".into(), capacity: 100, weight: 5 },
+            Link { link_id: 2, start: "A".into(), end: "C".into(), capacity: 100, weight: 1 },
+            Link { link_id: 3, start: "C".into(), end: "D".into(), capacity: 100, weight: 1 },
+        ];
+        let coords = [
+            ("A".to_string(), (0.0, 0.0, 0.0)),
+            ("B".to_string(), (0.0, 4.0, 0.0)),
+            ("C".to_string(), (0.5, 0.5, 0.0)),
+            ("D".to_string(), (1.0, 1.0, 0.0)),
+        ]
+        .into_iter()
+        .collect();
+
+        Network { links, coords }
+    }
+
+    #[test]
+    fn dijkstra_and_bfs_reconstruct_paths_through_the_network() {
+        let network = diamond_network();
+
+        let shortest = dijkstra(&network, "A", "D").unwrap();
+        assert_eq!(path_weight(&network, &shortest), 2);
+        assert_eq!(shortest, vec![2, 3]);
+
+        let fewest_hops = bfs(&network, "A", "D").unwrap();
+        assert_eq!(fewest_hops.len(), 2);
+    }
+
+    #[test]
+    fn astar_and_greedy_find_the_low_weight_path_using_coordinates() {
+        let network = diamond_network();
+
+        let astar_path = find_path(&network, "A", "D", RoutingMode::AStar, 1.0).unwrap();
+        assert_eq!(path_weight(&network, &astar_path), 2);
+
+        let greedy_path = find_path(&network, "A", "D", RoutingMode::Greedy, 1.0).unwrap();
+        assert_eq!(path_weight(&network, &greedy_path), 2);
+    }
+
+    #[test]
+    fn astar_falls_back_to_dijkstra_behavior_without_coordinates() {
+        let mut network = diamond_network();
+        network.coords.clear();
+
+        let path = find_path(&network, "A", "D", RoutingMode::AStar, 1.0).unwrap();
+        assert_eq!(path_weight(&network, &path), 2);
+    }
+
+    #[test]
+    fn k_shortest_paths_are_loopless_and_ordered_by_weight() {
+        let network = diamond_network();
+
+        let paths = k_shortest_paths(&network, "A", "D", 2);
+        assert_eq!(paths.len(), 2);
+
+        // Each path visits every node at most once.
+        for path in &paths {
+            let mut nodes: Vec<&str> = path
+                .iter()
+                .flat_map(|&link_id| [network.links[link_id].start.as_str(), network.links[link_id].end.as_str()])
+                .collect();
+            nodes.sort();
+            nodes.dedup();
+            assert_eq!(nodes.len(), path.len() + 1);
+        }
+
+        let weights: Vec<usize> = paths.iter().map(|path| path_weight(&network, path)).collect();
+        assert!(weights.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn split_demand_allocates_the_full_demand_across_paths() {
+        let allocations = split_demand(&[5, 5, 0], 13);
+        assert_eq!(allocations.iter().sum::<usize>(), 13);
+    }
+
+    #[test]
+    fn simulate_link_failure_reroutes_around_the_failed_link() {
+        let network = diamond_network();
+        let demands = vec![TrafficDemand { source: "A".into(), destination: "D".into(), demand: 1 }];
+
+        // Failing the cheap A-C-D path (link 2) still leaves A-B-D.
+        let result = simulate_link_failure(&network, &demands, 2, None);
+        assert_eq!(result.unroutable_demands, 0);
+    }
+
+    #[test]
+    fn simulate_link_failure_marks_a_demand_unroutable_with_no_alternate_path() {
+        // A single chain A -> B -> C with no alternate route.
+        let links = vec![
+            Link { link_id: 0, start: "A".into(), end: "B".into(), capacity: 100, weight: 1 },
+            Link { link_id: 1, start: "B".into(), end: "C".into(), capacity: 100, weight: 1 },
+        ];
+        let network = Network { links, coords: HashMap::new() };
+        let demands = vec![TrafficDemand { source: "A".into(), destination: "C".into(), demand: 1 }];
+
+        let result = simulate_link_failure(&network, &demands, 0, None);
+        assert_eq!(result.unroutable_demands, 1);
+    }
+
+    #[test]
+    fn network_hash_is_stable_and_order_independent() {
+        let network = diamond_network();
+        let mut reordered = diamond_network();
+        reordered.links.reverse();
+
+        assert_eq!(network_hash(&network), network_hash(&network));
+        assert_eq!(network_hash(&network), network_hash(&reordered));
+    }
+
+    #[test]
+    fn route_cache_round_trips_through_bincode() {
+        let network = diamond_network();
+        let cache = precompute_routes(&network);
+
+        let file_path = std::env::temp_dir().join("network_modeller_route_cache_round_trip_test.idx");
+        save_route_cache(&cache, file_path.to_str().unwrap()).unwrap();
+        let loaded = load_route_cache(file_path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&file_path).unwrap();
+
+        assert_eq!(loaded.network_hash, cache.network_hash);
+        assert_eq!(loaded.network_hash, network_hash(&network));
+    }
+
+    #[test]
+    fn find_path_cached_matches_plain_dijkstra() {
+        let network = diamond_network();
+        let cache = precompute_routes(&network);
+
+        let cached = find_path_cached(&network, &cache, "A", "D").unwrap();
+        let direct = dijkstra(&network, "A", "D").unwrap();
+        assert_eq!(path_weight(&network, &cached), path_weight(&network, &direct));
+    }
+
+    // A chain S -> W1 -> ... -> W9 -> D, with `isolate_last` controlling
+    // whether W9 keeps its links (reachable) or loses them (unreachable),
+    // used to exercise the nearest-neighbor fallback (9 free waypoints is
+    // above `WAYPOINT_PERMUTATION_LIMIT`).
+    fn waypoint_chain_network(isolate_last: bool) -> (Network, Vec<String>) {
+        let nodes: Vec<String> = std::iter::once("S".to_string())
+            .chain((1..=9).map(|i| format!("W{i}")))
+            .chain(std::iter::once("D".to_string()))
+            .collect();
+
+        let mut links = Vec::new();
+        for (i, pair) in nodes.windows(2).enumerate() {
+            if isolate_last && (pair[0] == "W9" || pair[1] == "W9") {
+                continue;
+            }
+            links.push(Link { link_id: i, start: pair[0].clone(), end: pair[1].clone(), capacity: 100, weight: 1 });
+        }
+
+        let waypoints: Vec<String> = (1..=9).map(|i| format!("W{i}")).collect();
+        (Network { links, coords: HashMap::new() }, waypoints)
+    }
+
+    #[test]
+    fn route_via_waypoints_uses_nearest_neighbor_fallback_above_the_permutation_limit() {
+        let (network, waypoints) = waypoint_chain_network(false);
+
+        let path = route_via_waypoints(&network, "S", "D", &waypoints, false, false).unwrap();
+        assert_eq!(path.len(), 10);
+    }
+
+    #[test]
+    fn route_via_waypoints_fails_rather_than_dropping_an_unreachable_waypoint() {
+        let (network, waypoints) = waypoint_chain_network(true);
+
+        assert!(route_via_waypoints(&network, "S", "D", &waypoints, false, false).is_none());
+    }
+}